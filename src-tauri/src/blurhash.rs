@@ -0,0 +1,119 @@
+//! Minimal BlurHash encoder (https://blurha.sh) so the UI can paint an
+//! instant placeholder for a frame before the full JPEG has decoded.
+
+use image::RgbImage;
+
+const BASE83_ALPHABET: &str =
+    "0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz#$%*+,-.:;=?@[]^_{|}~";
+
+fn encode_base83(mut value: u32, length: usize) -> String {
+    let alphabet: Vec<char> = BASE83_ALPHABET.chars().collect();
+    let mut digits = vec!['0'; length];
+    for slot in digits.iter_mut().rev() {
+        let digit = (value % 83) as usize;
+        *slot = alphabet[digit];
+        value /= 83;
+    }
+    digits.into_iter().collect()
+}
+
+fn srgb_to_linear(channel: u8) -> f64 {
+    let v = channel as f64 / 255.0;
+    if v <= 0.04045 {
+        v / 12.92
+    } else {
+        ((v + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn linear_to_srgb(value: f64) -> u8 {
+    let v = value.clamp(0.0, 1.0);
+    let srgb = if v <= 0.0031308 {
+        v * 12.92
+    } else {
+        1.055 * v.powf(1.0 / 2.4) - 0.055
+    };
+    (srgb * 255.0).round().clamp(0.0, 255.0) as u8
+}
+
+fn sign_pow(value: f64, exp: f64) -> f64 {
+    value.signum() * value.abs().powf(exp)
+}
+
+/// The DCT component for one `(i, j)` pair as a linear-light RGB triple;
+/// `(0, 0)` gives the average color, every other pair an AC term.
+fn basis_component(img: &RgbImage, i: u32, j: u32) -> [f64; 3] {
+    let (width, height) = (img.width(), img.height());
+    let normalisation = if i == 0 && j == 0 { 1.0 } else { 2.0 };
+    let mut sum = [0.0; 3];
+
+    for y in 0..height {
+        for x in 0..width {
+            let basis = (std::f64::consts::PI * i as f64 * x as f64 / width as f64).cos()
+                * (std::f64::consts::PI * j as f64 * y as f64 / height as f64).cos();
+            let pixel = img.get_pixel(x, y);
+            sum[0] += basis * srgb_to_linear(pixel[0]);
+            sum[1] += basis * srgb_to_linear(pixel[1]);
+            sum[2] += basis * srgb_to_linear(pixel[2]);
+        }
+    }
+
+    let scale = normalisation / (width * height) as f64;
+    [sum[0] * scale, sum[1] * scale, sum[2] * scale]
+}
+
+fn encode_dc(rgb: [f64; 3]) -> u32 {
+    let r = linear_to_srgb(rgb[0]) as u32;
+    let g = linear_to_srgb(rgb[1]) as u32;
+    let b = linear_to_srgb(rgb[2]) as u32;
+    (r << 16) + (g << 8) + b
+}
+
+fn encode_ac(rgb: [f64; 3], max_value: f64) -> u32 {
+    let quantize = |value: f64| -> u32 {
+        (sign_pow(value / max_value, 0.5) * 9.0 + 9.5)
+            .floor()
+            .clamp(0.0, 18.0) as u32
+    };
+    quantize(rgb[0]) * 19 * 19 + quantize(rgb[1]) * 19 + quantize(rgb[2])
+}
+
+/// Encodes `img` into a BlurHash string using `num_x` x `num_y` DCT
+/// components (a typical choice is 4x3).
+pub fn encode(img: &RgbImage, num_x: u32, num_y: u32) -> String {
+    let mut factors = Vec::with_capacity((num_x * num_y) as usize);
+    for j in 0..num_y {
+        for i in 0..num_x {
+            factors.push(basis_component(img, i, j));
+        }
+    }
+
+    let dc = factors[0];
+    let ac = &factors[1..];
+
+    let size_flag = (num_x - 1) + (num_y - 1) * 9;
+
+    let mut hash = encode_base83(size_flag, 1);
+
+    if ac.is_empty() {
+        hash.push_str(&encode_base83(0, 1));
+        hash.push_str(&encode_base83(encode_dc(dc), 4));
+        return hash;
+    }
+
+    let max_ac = ac
+        .iter()
+        .flat_map(|rgb| rgb.iter().copied())
+        .fold(0.0_f64, |acc, value| acc.max(value.abs()));
+    let quantized_max_value = ((max_ac * 166.0 - 0.5).floor().max(0.0).min(82.0)) as u32;
+    let actual_max_value = (quantized_max_value + 1) as f64 / 166.0;
+
+    hash.push_str(&encode_base83(quantized_max_value, 1));
+    hash.push_str(&encode_base83(encode_dc(dc), 4));
+
+    for rgb in ac {
+        hash.push_str(&encode_base83(encode_ac(*rgb, actual_max_value), 2));
+    }
+
+    hash
+}
@@ -0,0 +1,153 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use futures_util::StreamExt;
+use tokio::sync::{broadcast, oneshot};
+use tokio_tungstenite::tungstenite::Message;
+
+use crate::AppState;
+
+/// A single frame read off a camera's WebSocket stream, timestamped at the
+/// moment it was received so consumers (recording, in particular) can place
+/// it on a wall-clock timeline.
+#[derive(Clone)]
+pub struct TappedFrame {
+    pub bytes: Arc<Vec<u8>>,
+    pub received_at_unix_secs: f64,
+}
+
+/// One live WebSocket connection to a camera's stream endpoint, shared by
+/// every consumer (live view, recording) instead of each dialing its own.
+struct CameraTap {
+    task: tauri::async_runtime::JoinHandle<()>,
+    cancel: Option<oneshot::Sender<()>>,
+    sender: broadcast::Sender<TappedFrame>,
+    subscribers: usize,
+}
+
+impl Drop for CameraTap {
+    fn drop(&mut self) {
+        if let Some(cancel) = self.cancel.take() {
+            let _ = cancel.send(());
+        }
+        self.task.abort();
+    }
+}
+
+pub type TapRegistry = Arc<Mutex<HashMap<String, CameraTap>>>;
+
+pub fn new_registry() -> TapRegistry {
+    Arc::new(Mutex::new(HashMap::new()))
+}
+
+fn ws_url(api_base_url: &str, camera_id: &str) -> String {
+    let ws_base = api_base_url
+        .replacen("https://", "wss://", 1)
+        .replacen("http://", "ws://", 1);
+    format!("{}/api/cameras/{}/stream", ws_base, camera_id)
+}
+
+fn unix_now() -> f64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs_f64()
+}
+
+/// Subscribes to `camera_id`'s frame stream, dialing a new WebSocket
+/// connection only if no tap for this camera is already running. Each
+/// caller is responsible for calling `release` exactly once when it's done
+/// consuming frames.
+pub async fn acquire(state: &AppState, camera_id: &str) -> Result<broadcast::Receiver<TappedFrame>, String> {
+    {
+        let mut taps = state.camera_taps.lock().unwrap();
+        if let Some(tap) = taps.get_mut(camera_id) {
+            tap.subscribers += 1;
+            return Ok(tap.sender.subscribe());
+        }
+    }
+
+    let url = ws_url(&state.base_url(), camera_id);
+    let (ws_stream, _) = tokio_tungstenite::connect_async(&url)
+        .await
+        .map_err(|e| format!("Failed to open camera stream for {}: {}", camera_id, e))?;
+
+    let mut taps = state.camera_taps.lock().unwrap();
+    if let Some(tap) = taps.get_mut(camera_id) {
+        // Another caller won the race while we were connecting; use theirs
+        // and let this freshly-dialed connection drop.
+        tap.subscribers += 1;
+        return Ok(tap.sender.subscribe());
+    }
+
+    let (sender, receiver) = broadcast::channel(64);
+    let (cancel_tx, mut cancel_rx) = oneshot::channel();
+    let task_camera_id = camera_id.to_string();
+    let task_sender = sender.clone();
+
+    let task = tauri::async_runtime::spawn(async move {
+        let (_, mut read) = ws_stream.split();
+
+        loop {
+            tokio::select! {
+                _ = &mut cancel_rx => break,
+                msg = read.next() => {
+                    match msg {
+                        Some(Ok(Message::Binary(frame))) => {
+                            let _ = task_sender.send(TappedFrame {
+                                bytes: Arc::new(frame),
+                                received_at_unix_secs: unix_now(),
+                            });
+                        }
+                        Some(Ok(Message::Close(_))) | None => break,
+                        Some(Err(e)) => {
+                            eprintln!("Camera stream error for {}: {}", task_camera_id, e);
+                            break;
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        }
+    });
+
+    taps.insert(
+        camera_id.to_string(),
+        CameraTap {
+            task,
+            cancel: Some(cancel_tx),
+            sender,
+            subscribers: 1,
+        },
+    );
+
+    Ok(receiver)
+}
+
+/// Releases one subscription acquired via `acquire`. Once the last
+/// subscriber releases, the underlying WebSocket connection is closed.
+pub fn release(state: &AppState, camera_id: &str) {
+    let mut taps = state.camera_taps.lock().unwrap();
+    if let Some(tap) = taps.get_mut(camera_id) {
+        tap.subscribers = tap.subscribers.saturating_sub(1);
+        if tap.subscribers == 0 {
+            taps.remove(camera_id);
+        }
+    }
+}
+
+/// Releases a subscription acquired via `acquire` when dropped, whether the
+/// holding task runs to completion or is aborted mid-flight — abort drops
+/// the task's future (and everything it holds across an `.await`) without
+/// running any code after the point it was suspended, so the release has to
+/// live in a destructor, not in a line of code after a loop.
+pub struct TapReleaseGuard {
+    pub state: Arc<AppState>,
+    pub camera_id: String,
+}
+
+impl Drop for TapReleaseGuard {
+    fn drop(&mut self) {
+        release(&self.state, &self.camera_id);
+    }
+}
@@ -0,0 +1,142 @@
+use std::fs;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, State};
+
+use crate::AppState;
+
+const CONFIG_FILE_NAME: &str = "sentinel.toml";
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TlsConfig {
+    pub ca_cert_path: Option<String>,
+    pub client_cert_path: Option<String>,
+    pub client_key_path: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SentinelConfig {
+    pub api_base_url: String,
+    pub request_timeout_secs: u64,
+    pub auth_token: Option<String>,
+    pub tls: Option<TlsConfig>,
+}
+
+impl Default for SentinelConfig {
+    fn default() -> Self {
+        Self {
+            api_base_url: "http://localhost:8765".to_string(),
+            request_timeout_secs: 5,
+            auth_token: None,
+            tls: None,
+        }
+    }
+}
+
+impl SentinelConfig {
+    fn config_path(app_handle: &AppHandle) -> Result<PathBuf, String> {
+        let dir = app_handle
+            .path()
+            .app_config_dir()
+            .map_err(|e| format!("Failed to resolve app config directory: {}", e))?;
+        Ok(dir.join(CONFIG_FILE_NAME))
+    }
+
+    /// Loads `sentinel.toml` from the app config directory, then applies
+    /// env-var overrides on top so deployments can point the shell at a
+    /// remote sentinel server without touching the file.
+    pub fn load(app_handle: &AppHandle) -> Result<Self, String> {
+        let path = Self::config_path(app_handle)?;
+
+        let mut config = if path.exists() {
+            let contents = fs::read_to_string(&path)
+                .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+            toml::from_str(&contents)
+                .map_err(|e| format!("Failed to parse {}: {}", path.display(), e))?
+        } else {
+            Self::default()
+        };
+
+        if let Ok(url) = std::env::var("SENTINEL_API_BASE_URL") {
+            config.api_base_url = url;
+        }
+        if let Ok(token) = std::env::var("SENTINEL_AUTH_TOKEN") {
+            config.auth_token = Some(token);
+        }
+        if let Ok(secs) = std::env::var("SENTINEL_REQUEST_TIMEOUT_SECS") {
+            if let Ok(secs) = secs.parse() {
+                config.request_timeout_secs = secs;
+            }
+        }
+
+        Ok(config)
+    }
+
+    pub fn save(&self, app_handle: &AppHandle) -> Result<(), String> {
+        let path = Self::config_path(app_handle)?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+        }
+        let contents =
+            toml::to_string_pretty(self).map_err(|e| format!("Failed to serialize config: {}", e))?;
+        fs::write(&path, contents).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    }
+
+    /// Builds a `reqwest::Client` for this config. When the base URL is
+    /// `https`, the client is built on rustls and trusts the configured CA
+    /// certificate, optionally presenting a client cert/key pair for mTLS.
+    pub fn build_client(&self) -> Result<reqwest::Client, String> {
+        let mut builder = reqwest::Client::builder();
+
+        if self.api_base_url.starts_with("https://") {
+            builder = builder.use_rustls_tls();
+
+            if let Some(tls) = &self.tls {
+                if let Some(ca_path) = &tls.ca_cert_path {
+                    let pem = fs::read(ca_path)
+                        .map_err(|e| format!("Failed to read CA cert {}: {}", ca_path, e))?;
+                    let cert = reqwest::Certificate::from_pem(&pem)
+                        .map_err(|e| format!("Invalid CA cert {}: {}", ca_path, e))?;
+                    builder = builder.add_root_certificate(cert);
+                }
+
+                if let (Some(cert_path), Some(key_path)) = (&tls.client_cert_path, &tls.client_key_path) {
+                    let mut identity_pem = fs::read(cert_path)
+                        .map_err(|e| format!("Failed to read client cert {}: {}", cert_path, e))?;
+                    let mut key_pem = fs::read(key_path)
+                        .map_err(|e| format!("Failed to read client key {}: {}", key_path, e))?;
+                    identity_pem.append(&mut key_pem);
+                    let identity = reqwest::Identity::from_pem(&identity_pem)
+                        .map_err(|e| format!("Invalid client identity: {}", e))?;
+                    builder = builder.identity(identity);
+                }
+            }
+        }
+
+        builder
+            .build()
+            .map_err(|e| format!("Failed to build HTTP client: {}", e))
+    }
+}
+
+#[tauri::command]
+pub async fn get_config(state: State<'_, AppState>) -> Result<SentinelConfig, String> {
+    Ok(state.config.lock().unwrap().clone())
+}
+
+#[tauri::command]
+pub async fn set_config(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+    config: SentinelConfig,
+) -> Result<(), String> {
+    let client = config.build_client()?;
+    config.save(&app_handle)?;
+
+    *state.config.lock().unwrap() = config;
+    *state.client.lock().unwrap() = client;
+
+    Ok(())
+}
@@ -1,10 +1,35 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU32, Ordering};
 use std::sync::{Arc, Mutex};
 use std::process::{Child, Command, Stdio};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use tauri::{AppHandle, Emitter, State};
 use serde::{Deserialize, Serialize};
 use anyhow::Result;
 
+mod streaming;
+use streaming::StreamRegistry;
+
+mod camera_tap;
+use camera_tap::TapRegistry;
+
+mod supervisor;
+mod metrics;
+
+mod config;
+use config::SentinelConfig;
+
+mod blurhash;
+use base64::Engine;
+
+mod vault;
+use vault::Vault;
+
+mod recording;
+use recording::RecordingRegistry;
+
+const METRICS_PORT: u16 = 9090;
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct DashboardData {
     pub alerts: serde_json::Value,
@@ -17,23 +42,61 @@ pub struct SystemStatus {
     pub backend_running: bool,
     pub python_pid: Option<u32>,
     pub last_update: f64,
+    pub backend_restart_count: u32,
+    pub active_recordings: usize,
 }
 
 #[derive(Clone)]
 pub struct AppState {
     pub python_process: Arc<Mutex<Option<Child>>>,
-    pub api_base_url: String,
-    pub client: reqwest::Client,
+    pub config: Arc<Mutex<SentinelConfig>>,
+    pub client: Arc<Mutex<reqwest::Client>>,
+    pub camera_streams: StreamRegistry,
+    pub camera_taps: TapRegistry,
+    pub backend_restart_count: Arc<AtomicU32>,
+    pub vault: Arc<Vault>,
+    pub recordings: RecordingRegistry,
 }
 
 impl AppState {
     pub fn new() -> Self {
         Self {
             python_process: Arc::new(Mutex::new(None)),
-            api_base_url: "http://localhost:8765".to_string(),
-            client: reqwest::Client::new(),
+            config: Arc::new(Mutex::new(SentinelConfig::default())),
+            client: Arc::new(Mutex::new(reqwest::Client::new())),
+            camera_streams: Arc::new(Mutex::new(HashMap::new())),
+            camera_taps: camera_tap::new_registry(),
+            backend_restart_count: Arc::new(AtomicU32::new(0)),
+            vault: Arc::new(Vault::new()),
+            recordings: Arc::new(Mutex::new(HashMap::new())),
         }
     }
+
+    /// Currently configured backend base URL.
+    pub fn base_url(&self) -> String {
+        self.config.lock().unwrap().api_base_url.clone()
+    }
+
+    /// A cheap clone of the current HTTP client (rebuilt whenever the config
+    /// changes via `set_config`).
+    pub fn http_client(&self) -> reqwest::Client {
+        self.client.lock().unwrap().clone()
+    }
+
+    /// Attaches the configured client-auth token as a bearer header, if one
+    /// is set.
+    pub fn authed(&self, builder: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        match self.config.lock().unwrap().auth_token.clone() {
+            Some(token) => builder.bearer_auth(token),
+            None => builder,
+        }
+    }
+
+    /// The configured request timeout, read fresh so changes from
+    /// `set_config` take effect on the next call without restarting.
+    pub fn request_timeout(&self) -> Duration {
+        Duration::from_secs(self.config.lock().unwrap().request_timeout_secs)
+    }
 }
 
 // Internal function for backend startup (used in setup and command)
@@ -61,10 +124,24 @@ async fn start_python_backend_internal(state: Arc<AppState>) -> Result<bool, Str
         }
     }
 
-    // Additional check: test if port 8765 is already in use
-    if let Ok(_) = std::net::TcpStream::connect("127.0.0.1:8765") {
-        println!("Port 8765 already in use, backend likely already running");
-        return Ok(true);
+    // Additional check: if something is already listening on 8765, only
+    // treat it as "our" backend if we can find a prior PID that owns the
+    // socket; otherwise it's an unrelated process and we should still try
+    // to start our own.
+    if supervisor::port_in_use(8765) {
+        let owned_by_us = python_process
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map(|child| supervisor::port_owned_by_pid(8765, child.id()))
+            .unwrap_or(false);
+
+        if owned_by_us {
+            println!("Port 8765 already in use by our own backend process");
+            return Ok(true);
+        }
+
+        println!("Port 8765 is in use by an unrelated process; starting our backend anyway");
     }
 
     // Start Python backend with virtual environment
@@ -120,11 +197,17 @@ async fn stop_python_backend(state: State<'_, AppState>) -> Result<bool, String>
 
 #[tauri::command]
 async fn get_dashboard_data(state: State<'_, AppState>) -> Result<DashboardData, String> {
-    let url = format!("{}/api/dashboard", state.api_base_url);
+    let start = Instant::now();
+    let result = get_dashboard_data_internal(&state).await;
+    metrics::record_api_call("get_dashboard_data", start, result.is_ok());
+    result
+}
+
+async fn get_dashboard_data_internal(state: &AppState) -> Result<DashboardData, String> {
+    let url = format!("{}/api/dashboard", state.base_url());
     
-    let response = state.client
-        .get(&url)
-        .timeout(Duration::from_secs(5))
+    let response = state.authed(state.http_client().get(&url))
+        .timeout(state.request_timeout())
         .send()
         .await
         .map_err(|e| format!("Failed to fetch dashboard data: {}", e))?;
@@ -133,30 +216,88 @@ async fn get_dashboard_data(state: State<'_, AppState>) -> Result<DashboardData,
         return Err(format!("API error: {}", response.status()));
     }
 
-    let data: DashboardData = response.json()
+    let mut data: DashboardData = response.json()
         .await
         .map_err(|e| format!("Failed to parse dashboard data: {}", e))?;
 
+    enrich_alerts_with_blurhash(&mut data.alerts);
+
     Ok(data)
 }
 
+/// Attaches a `blurhash` field to each alert that carries a `thumbnail`
+/// (base64 JPEG), so the UI can paint a placeholder before the real image
+/// loads. Alerts without a thumbnail are left untouched.
+fn enrich_alerts_with_blurhash(alerts: &mut serde_json::Value) {
+    let Some(alerts) = alerts.as_array_mut() else {
+        return;
+    };
+
+    for alert in alerts.iter_mut() {
+        let Some(thumbnail) = alert.get("thumbnail").and_then(|v| v.as_str()) else {
+            continue;
+        };
+
+        if let Ok(hash) = blurhash_from_base64_jpeg(thumbnail) {
+            alert["blurhash"] = serde_json::Value::String(hash);
+        }
+    }
+}
+
+fn blurhash_from_base64_jpeg(frame_b64: &str) -> Result<String, String> {
+    let bytes = base64::engine::general_purpose::STANDARD
+        .decode(frame_b64)
+        .map_err(|e| format!("Failed to decode frame as base64: {}", e))?;
+
+    let img = image::load_from_memory(&bytes)
+        .map_err(|e| format!("Failed to decode frame image: {}", e))?
+        .to_rgb8();
+
+    Ok(blurhash::encode(&img, 4, 3))
+}
+
+#[tauri::command]
+async fn blurhash_for_frame(
+    state: State<'_, AppState>,
+    camera_id: String,
+) -> Result<String, String> {
+    let frame = get_camera_frame_internal(&state, camera_id).await?;
+
+    let frame_b64 = frame
+        .get("frame")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| "Camera frame response did not include a \"frame\" field".to_string())?;
+
+    blurhash_from_base64_jpeg(frame_b64)
+}
+
 #[tauri::command(rename_all = "snake_case")]
 async fn update_threshold(
     state: State<'_, AppState>,
     threshold_name: String,
     value: f64
 ) -> Result<bool, String> {
-    let url = format!("{}/api/threshold", state.api_base_url);
+    let start = Instant::now();
+    let result = update_threshold_internal(&state, threshold_name, value).await;
+    metrics::record_api_call("update_threshold", start, result.is_ok());
+    result
+}
+
+async fn update_threshold_internal(
+    state: &AppState,
+    threshold_name: String,
+    value: f64
+) -> Result<bool, String> {
+    let url = format!("{}/api/threshold", state.base_url());
     
     let payload = serde_json::json!({
         "threshold_name": threshold_name,
         "value": value
     });
 
-    let response = state.client
-        .post(&url)
+    let response = state.authed(state.http_client().post(&url))
         .json(&payload)
-        .timeout(Duration::from_secs(5))
+        .timeout(state.request_timeout())
         .send()
         .await
         .map_err(|e| format!("Failed to update threshold: {}", e))?;
@@ -178,16 +319,25 @@ async fn acknowledge_alert(
     state: State<'_, AppState>,
     alert_id: String
 ) -> Result<bool, String> {
-    let url = format!("{}/api/acknowledge", state.api_base_url);
+    let start = Instant::now();
+    let result = acknowledge_alert_internal(&state, alert_id).await;
+    metrics::record_api_call("acknowledge_alert", start, result.is_ok());
+    result
+}
+
+async fn acknowledge_alert_internal(
+    state: &AppState,
+    alert_id: String
+) -> Result<bool, String> {
+    let url = format!("{}/api/acknowledge", state.base_url());
     
     let payload = serde_json::json!({
         "alert_id": alert_id
     });
 
-    let response = state.client
-        .post(&url)
+    let response = state.authed(state.http_client().post(&url))
         .json(&payload)
-        .timeout(Duration::from_secs(5))
+        .timeout(state.request_timeout())
         .send()
         .await
         .map_err(|e| format!("Failed to acknowledge alert: {}", e))?;
@@ -206,11 +356,17 @@ async fn acknowledge_alert(
 
 #[tauri::command]
 async fn get_camera_feeds(state: State<'_, AppState>) -> Result<serde_json::Value, String> {
-    let url = format!("{}/api/cameras", state.api_base_url);
+    let start = Instant::now();
+    let result = get_camera_feeds_internal(&state).await;
+    metrics::record_api_call("get_camera_feeds", start, result.is_ok());
+    result
+}
+
+async fn get_camera_feeds_internal(state: &AppState) -> Result<serde_json::Value, String> {
+    let url = format!("{}/api/cameras", state.base_url());
     
-    let response = state.client
-        .get(&url)
-        .timeout(Duration::from_secs(5))
+    let response = state.authed(state.http_client().get(&url))
+        .timeout(state.request_timeout())
         .send()
         .await
         .map_err(|e| format!("Failed to fetch camera feeds: {}", e))?;
@@ -243,6 +399,8 @@ async fn get_system_status(state: State<'_, AppState>) -> Result<SystemStatus, S
             .duration_since(std::time::UNIX_EPOCH)
             .unwrap()
             .as_secs_f64(),
+        backend_restart_count: state.backend_restart_count.load(Ordering::Relaxed),
+        active_recordings: state.recordings.lock().unwrap().len(),
     })
 }
 
@@ -251,11 +409,20 @@ async fn get_camera_frame(
     state: State<'_, AppState>,
     camera_id: String
 ) -> Result<serde_json::Value, String> {
-    let url = format!("{}/api/cameras/{}/frame", state.api_base_url, camera_id);
+    let start = Instant::now();
+    let result = get_camera_frame_internal(&state, camera_id).await;
+    metrics::record_api_call("get_camera_frame", start, result.is_ok());
+    result
+}
+
+async fn get_camera_frame_internal(
+    state: &AppState,
+    camera_id: String
+) -> Result<serde_json::Value, String> {
+    let url = format!("{}/api/cameras/{}/frame", state.base_url(), camera_id);
     
-    let response = state.client
-        .get(&url)
-        .timeout(Duration::from_secs(5))
+    let response = state.authed(state.http_client().get(&url))
+        .timeout(state.request_timeout())
         .send()
         .await
         .map_err(|e| format!("Failed to fetch camera frame: {}", e))?;
@@ -273,11 +440,10 @@ async fn get_camera_frame(
 
 #[tauri::command]
 async fn get_system_metrics(state: State<'_, AppState>) -> Result<serde_json::Value, String> {
-    let url = format!("{}/api/metrics", state.api_base_url);
+    let url = format!("{}/api/metrics", state.base_url());
     
-    let response = state.client
-        .get(&url)
-        .timeout(Duration::from_secs(5))
+    let response = state.authed(state.http_client().get(&url))
+        .timeout(state.request_timeout())
         .send()
         .await
         .map_err(|e| format!("Failed to fetch system metrics: {}", e))?;
@@ -298,16 +464,25 @@ async fn discover_cameras(
     state: State<'_, AppState>,
     timeout: Option<u32>
 ) -> Result<serde_json::Value, String> {
-    let url = format!("{}/api/cameras/discover", state.api_base_url);
+    let start = Instant::now();
+    let result = discover_cameras_internal(&state, timeout).await;
+    metrics::record_api_call("discover_cameras", start, result.is_ok());
+    result
+}
+
+async fn discover_cameras_internal(
+    state: &AppState,
+    timeout: Option<u32>
+) -> Result<serde_json::Value, String> {
+    let url = format!("{}/api/cameras/discover", state.base_url());
     
     let payload = serde_json::json!({
         "timeout": timeout.unwrap_or(5)
     });
 
-    let response = state.client
-        .post(&url)
+    let response = state.authed(state.http_client().post(&url))
         .json(&payload)
-        .timeout(Duration::from_secs(10))
+        .timeout(state.request_timeout())
         .send()
         .await
         .map_err(|e| format!("Failed to discover cameras: {}", e))?;
@@ -325,31 +500,42 @@ async fn discover_cameras(
 
 #[tauri::command]
 async fn add_camera(
+    app_handle: AppHandle,
     state: State<'_, AppState>,
     camera_id: String,
     rtsp_url: String,
-    username: Option<String>,
-    password: Option<String>
+    credential_id: Option<String>
 ) -> Result<serde_json::Value, String> {
-    let url = format!("{}/api/cameras/add", state.api_base_url);
-    
+    let start = Instant::now();
+    let result = add_camera_internal(&app_handle, &state, camera_id, rtsp_url, credential_id).await;
+    metrics::record_api_call("add_camera", start, result.is_ok());
+    result
+}
+
+async fn add_camera_internal(
+    app_handle: &AppHandle,
+    state: &AppState,
+    camera_id: String,
+    rtsp_url: String,
+    credential_id: Option<String>
+) -> Result<serde_json::Value, String> {
+    let url = format!("{}/api/cameras/add", state.base_url());
+
     let mut payload = serde_json::json!({
         "camera_id": camera_id,
         "rtsp_url": rtsp_url,
         "enabled": true
     });
 
-    if let Some(user) = username {
-        payload["username"] = serde_json::Value::String(user);
-    }
-    if let Some(pass) = password {
-        payload["password"] = serde_json::Value::String(pass);
+    if let Some(credential_id) = credential_id {
+        let credential = state.vault.decrypt_credential(app_handle, &credential_id)?;
+        payload["username"] = serde_json::Value::String(credential.username.clone());
+        payload["password"] = serde_json::Value::String(credential.password.clone());
     }
 
-    let response = state.client
-        .post(&url)
+    let response = state.authed(state.http_client().post(&url))
         .json(&payload)
-        .timeout(Duration::from_secs(10))
+        .timeout(state.request_timeout())
         .send()
         .await
         .map_err(|e| format!("Failed to add camera: {}", e))?;
@@ -367,29 +553,40 @@ async fn add_camera(
 
 #[tauri::command]
 async fn test_camera(
+    app_handle: AppHandle,
     state: State<'_, AppState>,
     camera_id: String,
     rtsp_url: String,
-    username: Option<String>,
-    password: Option<String>
+    credential_id: Option<String>
 ) -> Result<serde_json::Value, String> {
-    let url = format!("{}/api/cameras/{}/test", state.api_base_url, camera_id);
-    
+    let start = Instant::now();
+    let result = test_camera_internal(&app_handle, &state, camera_id, rtsp_url, credential_id).await;
+    metrics::record_api_call("test_camera", start, result.is_ok());
+    result
+}
+
+async fn test_camera_internal(
+    app_handle: &AppHandle,
+    state: &AppState,
+    camera_id: String,
+    rtsp_url: String,
+    credential_id: Option<String>
+) -> Result<serde_json::Value, String> {
+    let url = format!("{}/api/cameras/{}/test", state.base_url(), camera_id);
+
     let mut payload = serde_json::json!({
         "rtsp_url": rtsp_url
     });
 
-    if let Some(user) = username {
-        payload["username"] = serde_json::Value::String(user);
-    }
-    if let Some(pass) = password {
-        payload["password"] = serde_json::Value::String(pass);
+    if let Some(credential_id) = credential_id {
+        let credential = state.vault.decrypt_credential(app_handle, &credential_id)?;
+        payload["username"] = serde_json::Value::String(credential.username.clone());
+        payload["password"] = serde_json::Value::String(credential.password.clone());
     }
 
-    let response = state.client
-        .post(&url)
+    let response = state.authed(state.http_client().post(&url))
         .json(&payload)
-        .timeout(Duration::from_secs(15))
+        .timeout(state.request_timeout())
         .send()
         .await
         .map_err(|e| format!("Failed to test camera: {}", e))?;
@@ -410,11 +607,20 @@ async fn remove_camera(
     state: State<'_, AppState>,
     camera_id: String
 ) -> Result<serde_json::Value, String> {
-    let url = format!("{}/api/cameras/{}/remove", state.api_base_url, camera_id);
+    let start = Instant::now();
+    let result = remove_camera_internal(&state, camera_id).await;
+    metrics::record_api_call("remove_camera", start, result.is_ok());
+    result
+}
+
+async fn remove_camera_internal(
+    state: &AppState,
+    camera_id: String
+) -> Result<serde_json::Value, String> {
+    let url = format!("{}/api/cameras/{}/remove", state.base_url(), camera_id);
     
-    let response = state.client
-        .delete(&url)
-        .timeout(Duration::from_secs(10))
+    let response = state.authed(state.http_client().delete(&url))
+        .timeout(state.request_timeout())
         .send()
         .await
         .map_err(|e| format!("Failed to remove camera: {}", e))?;
@@ -440,8 +646,9 @@ fn setup_real_time_data_stream(app_handle: AppHandle, state: Arc<AppState>) {
             interval.tick().await;
             
             // Try to fetch dashboard data and emit update
-            let url = format!("{}/api/dashboard", state.api_base_url);
-            if let Ok(response) = state.client.get(&url).timeout(Duration::from_secs(5)).send().await {
+            let url = format!("{}/api/dashboard", state.base_url());
+            let request = state.authed(state.http_client().get(&url));
+            if let Ok(response) = request.timeout(state.request_timeout()).send().await {
                 if let Ok(data) = response.json::<DashboardData>().await {
                     let _ = app_handle.emit("real-time-update", &data);
                 }
@@ -468,7 +675,30 @@ pub fn run() {
         .setup(move |app| {
             let app_handle = app.handle().clone();
             let state_clone = state_arc.clone();
-            
+
+            // Load sentinel.toml (with env-var overrides) and rebuild the
+            // HTTP client so TLS settings take effect from the start.
+            match SentinelConfig::load(&app_handle) {
+                Ok(loaded_config) => match loaded_config.build_client() {
+                    Ok(client) => {
+                        *state_clone.client.lock().unwrap() = client;
+                        *state_clone.config.lock().unwrap() = loaded_config;
+                    }
+                    Err(e) => eprintln!("Failed to build HTTP client from config: {}", e),
+                },
+                Err(e) => eprintln!("Failed to load sentinel.toml, using defaults: {}", e),
+            }
+
+            // Expose our own operational metrics for scraping alongside the
+            // Python backend's.
+            let metrics_port = std::env::var("SENTINEL_METRICS_PORT")
+                .ok()
+                .and_then(|p| p.parse().ok())
+                .unwrap_or(METRICS_PORT);
+            if let Err(e) = metrics::install_recorder(metrics_port) {
+                eprintln!("Failed to start metrics exporter: {}", e);
+            }
+
             // Auto-start Python backend
             let backend_state = state_clone.clone();
             tauri::async_runtime::spawn(async move {
@@ -478,8 +708,11 @@ pub fn run() {
             });
             
             // Setup real-time data streaming
-            setup_real_time_data_stream(app_handle, state_clone);
-            
+            setup_real_time_data_stream(app_handle.clone(), state_clone.clone());
+
+            // Supervise the backend: probe health and restart with backoff
+            supervisor::spawn(app_handle, state_clone);
+
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
@@ -496,7 +729,18 @@ pub fn run() {
             test_camera,
             remove_camera,
             get_system_status,
-            get_system_metrics
+            get_system_metrics,
+            streaming::start_camera_stream,
+            streaming::stop_camera_stream,
+            config::get_config,
+            config::set_config,
+            blurhash_for_frame,
+            vault::unlock_vault,
+            vault::lock_vault,
+            vault::store_credential,
+            recording::start_recording,
+            recording::stop_recording,
+            recording::export_clip
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
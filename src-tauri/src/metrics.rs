@@ -0,0 +1,36 @@
+use std::net::SocketAddr;
+use std::time::Instant;
+
+use metrics_exporter_prometheus::PrometheusBuilder;
+
+/// Starts the `/metrics` HTTP endpoint that exposes the Tauri shell's own
+/// operational metrics in Prometheus text format, so the desktop side can be
+/// scraped by the same monitoring that already watches the Python backend.
+pub fn install_recorder(port: u16) -> anyhow::Result<()> {
+    let addr: SocketAddr = ([0, 0, 0, 0], port).into();
+    PrometheusBuilder::new().with_http_listener(addr).install()?;
+    Ok(())
+}
+
+/// Records a completed proxy call to the Python backend: increments the
+/// per-command call/failure counters and observes upstream request latency.
+pub fn record_api_call(command: &'static str, start: Instant, success: bool) {
+    metrics::counter!("sentinel_api_calls_total", "command" => command).increment(1);
+    if !success {
+        metrics::counter!("sentinel_api_call_failures_total", "command" => command).increment(1);
+    }
+    metrics::histogram!("sentinel_backend_request_duration_seconds", "command" => command)
+        .record(start.elapsed().as_secs_f64());
+}
+
+pub fn set_backend_running(running: bool) {
+    metrics::gauge!("sentinel_backend_running").set(if running { 1.0 } else { 0.0 });
+}
+
+pub fn set_active_camera_streams(count: f64) {
+    metrics::gauge!("sentinel_active_camera_streams").set(count);
+}
+
+pub fn record_backend_restart() {
+    metrics::counter!("sentinel_backend_restarts_total").increment(1);
+}
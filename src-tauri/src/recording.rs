@@ -0,0 +1,403 @@
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, State};
+use tokio::sync::oneshot;
+
+use crate::camera_tap::{self, TapReleaseGuard};
+use crate::AppState;
+
+const TIMESCALE: u32 = 1000; // fragment durations are tracked in milliseconds
+
+/// Handle to an in-progress recording. Dropping it (or calling
+/// `stop_recording`) signals the tee task to finalize the file and stop.
+pub struct RecordingHandle {
+    task: tauri::async_runtime::JoinHandle<()>,
+    cancel: Option<oneshot::Sender<()>>,
+    pub path: PathBuf,
+}
+
+impl Drop for RecordingHandle {
+    fn drop(&mut self) {
+        if let Some(cancel) = self.cancel.take() {
+            let _ = cancel.send(());
+        }
+        self.task.abort();
+    }
+}
+
+pub type RecordingRegistry = Arc<Mutex<HashMap<String, RecordingHandle>>>;
+
+fn recordings_dir(app_handle: &AppHandle) -> Result<PathBuf, String> {
+    let dir = app_handle
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to resolve app data directory: {}", e))?
+        .join("recordings");
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    Ok(dir)
+}
+
+/// One line of a recording's `.index.jsonl` sidecar: where a single
+/// moof/mdat fragment lives in the recording file and when it was
+/// received, so `export_clip` can carve an arbitrary time range out of a
+/// recording instead of only matching whole files by their start time.
+#[derive(Serialize, Deserialize)]
+struct FragmentIndexEntry {
+    offset: u64,
+    length: u64,
+    timestamp: f64,
+}
+
+fn index_path(recording_path: &Path) -> PathBuf {
+    let mut name = recording_path.as_os_str().to_owned();
+    name.push(".index.jsonl");
+    PathBuf::from(name)
+}
+
+// -- Minimal fragmented-MP4 box writer --------------------------------------
+//
+// Each recorded frame is stored as a single sample inside its own
+// moof/mdat fragment, following the init segment (ftyp/moov) written once
+// up front. The moov box holds only mvhd and mvex: it declares no trak and
+// no sample description, so the file records each fragment's bytes, size
+// and duration but carries no codec information for a player to decode
+// the samples with.
+
+fn write_box(out: &mut Vec<u8>, fourcc: &[u8; 4], payload: &[u8]) {
+    let size = 8 + payload.len() as u32;
+    out.extend_from_slice(&size.to_be_bytes());
+    out.extend_from_slice(fourcc);
+    out.extend_from_slice(payload);
+}
+
+fn ftyp_box() -> Vec<u8> {
+    let mut payload = Vec::new();
+    payload.extend_from_slice(b"isom");
+    payload.extend_from_slice(&0u32.to_be_bytes());
+    payload.extend_from_slice(b"isom");
+    payload.extend_from_slice(b"iso5");
+    payload.extend_from_slice(b"mp42");
+    let mut out = Vec::new();
+    write_box(&mut out, b"ftyp", &payload);
+    out
+}
+
+fn moov_box() -> Vec<u8> {
+    // mvhd: version/flags(4) + times(8) + timescale(4) + duration(4) + rate(4)
+    // + zeroed volume/reserved/matrix/predefined(72) + next track id(4)
+    let mut mvhd = Vec::new();
+    mvhd.extend_from_slice(&0u32.to_be_bytes()); // version + flags
+    mvhd.extend_from_slice(&0u32.to_be_bytes()); // creation time
+    mvhd.extend_from_slice(&0u32.to_be_bytes()); // modification time
+    mvhd.extend_from_slice(&TIMESCALE.to_be_bytes());
+    mvhd.extend_from_slice(&0u32.to_be_bytes()); // duration (unknown, fragmented)
+    mvhd.extend_from_slice(&0x00010000u32.to_be_bytes()); // rate 1.0
+    mvhd.extend_from_slice(&[0u8; 72]); // volume(2), reserved(10), matrix(36), predefined(24)
+    mvhd.extend_from_slice(&2u32.to_be_bytes()); // next track id
+    let mut mvhd_box = Vec::new();
+    write_box(&mut mvhd_box, b"mvhd", &mvhd);
+
+    // trex: declares default sample values so the moof fragments can omit them
+    let mut trex = Vec::new();
+    trex.extend_from_slice(&0u32.to_be_bytes()); // version + flags
+    trex.extend_from_slice(&1u32.to_be_bytes()); // track id
+    trex.extend_from_slice(&1u32.to_be_bytes()); // default sample description index
+    trex.extend_from_slice(&0u32.to_be_bytes()); // default sample duration (per-fragment override)
+    trex.extend_from_slice(&0u32.to_be_bytes()); // default sample size (per-fragment override)
+    trex.extend_from_slice(&0x02000000u32.to_be_bytes()); // default sample flags (sync sample)
+    let mut trex_box = Vec::new();
+    write_box(&mut trex_box, b"trex", &trex);
+
+    let mut mvex_box = Vec::new();
+    write_box(&mut mvex_box, b"mvex", &trex_box);
+
+    let mut payload = Vec::new();
+    payload.extend_from_slice(&mvhd_box);
+    payload.extend_from_slice(&mvex_box);
+
+    let mut out = Vec::new();
+    write_box(&mut out, b"moov", &payload);
+    out
+}
+
+fn moof_and_mdat(sequence: u32, sample: &[u8], duration_ms: u32) -> Vec<u8> {
+    let mut mfhd = Vec::new();
+    mfhd.extend_from_slice(&0u32.to_be_bytes());
+    mfhd.extend_from_slice(&sequence.to_be_bytes());
+    let mut mfhd_box = Vec::new();
+    write_box(&mut mfhd_box, b"mfhd", &mfhd);
+
+    let mut tfhd = Vec::new();
+    tfhd.extend_from_slice(&0u32.to_be_bytes()); // version + flags
+    tfhd.extend_from_slice(&1u32.to_be_bytes()); // track id
+    let mut tfhd_box = Vec::new();
+    write_box(&mut tfhd_box, b"tfhd", &tfhd);
+
+    let mut tfdt = Vec::new();
+    tfdt.extend_from_slice(&0u32.to_be_bytes());
+    tfdt.extend_from_slice(&0u32.to_be_bytes()); // base media decode time (always 0; not tracked)
+    let mut tfdt_box = Vec::new();
+    write_box(&mut tfdt_box, b"tfdt", &tfdt);
+
+    // trun: one sample, size + duration inline, data offset patched below
+    let mut trun = Vec::new();
+    trun.extend_from_slice(&0x00000301u32.to_be_bytes()); // flags: data-offset, duration, size present (no first-sample-flags; we never write that field)
+    trun.extend_from_slice(&1u32.to_be_bytes()); // sample count
+    let data_offset_pos = trun.len();
+    trun.extend_from_slice(&0i32.to_be_bytes()); // data offset placeholder
+    trun.extend_from_slice(&duration_ms.to_be_bytes());
+    trun.extend_from_slice(&(sample.len() as u32).to_be_bytes());
+    let mut trun_box = Vec::new();
+    write_box(&mut trun_box, b"trun", &trun);
+
+    let mut traf_payload = Vec::new();
+    traf_payload.extend_from_slice(&tfhd_box);
+    traf_payload.extend_from_slice(&tfdt_box);
+    traf_payload.extend_from_slice(&trun_box);
+    let mut traf_box = Vec::new();
+    write_box(&mut traf_box, b"traf", &traf_payload);
+
+    let mut moof_payload = Vec::new();
+    moof_payload.extend_from_slice(&mfhd_box);
+    moof_payload.extend_from_slice(&traf_box);
+    let mut moof_box = Vec::new();
+    write_box(&mut moof_box, b"moof", &moof_payload);
+
+    // data offset in trun is relative to the start of the moof box, pointing
+    // at the sample bytes inside the following mdat box's payload. Locate the
+    // placeholder we left in trun_box by its absolute offset within moof_box:
+    // moof header + mfhd_box + traf header + tfhd_box + tfdt_box + trun header.
+    let trun_offset_in_moof =
+        8 + mfhd_box.len() + 8 + tfhd_box.len() + tfdt_box.len() + 8 + data_offset_pos;
+    let data_offset = moof_box.len() as i32 + 8;
+    moof_box[trun_offset_in_moof..trun_offset_in_moof + 4].copy_from_slice(&data_offset.to_be_bytes());
+
+    let mut mdat_box = Vec::new();
+    write_box(&mut mdat_box, b"mdat", sample);
+
+    let mut out = Vec::new();
+    out.extend_from_slice(&moof_box);
+    out.extend_from_slice(&mdat_box);
+    out
+}
+
+#[tauri::command]
+pub async fn start_recording(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+    camera_id: String,
+) -> Result<String, String> {
+    if let Some(existing) = state.recordings.lock().unwrap().get(&camera_id) {
+        return Ok(existing.path.to_string_lossy().into_owned());
+    }
+
+    // Tap into the same shared connection live view uses instead of dialing
+    // our own, so a camera that's both viewed and recorded only costs one
+    // backend WebSocket connection.
+    let mut frames = camera_tap::acquire(&state, &camera_id).await?;
+
+    let mut recordings = state.recordings.lock().unwrap();
+    if let Some(existing) = recordings.get(&camera_id) {
+        // Another call won the race while we were connecting.
+        camera_tap::release(&state, &camera_id);
+        return Ok(existing.path.to_string_lossy().into_owned());
+    }
+
+    let dir = recordings_dir(&app_handle)?;
+    let file_name = format!(
+        "{}-{}.mp4",
+        camera_id,
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+    );
+    let path = dir.join(file_name);
+
+    let init_segment = {
+        let mut buf = ftyp_box();
+        buf.extend_from_slice(&moov_box());
+        buf
+    };
+
+    let mut file = File::create(&path).map_err(|e| format!("Failed to create recording file: {}", e))?;
+    file.write_all(&init_segment)
+        .map_err(|e| format!("Failed to write init segment: {}", e))?;
+
+    let mut index_file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(index_path(&path))
+        .map_err(|e| format!("Failed to create recording index: {}", e))?;
+
+    let (cancel_tx, mut cancel_rx) = oneshot::channel();
+    let release_state = Arc::new(state.inner().clone());
+    let release_camera_id = camera_id.clone();
+
+    let task = tauri::async_runtime::spawn(async move {
+        let _release_guard = TapReleaseGuard {
+            state: release_state,
+            camera_id: release_camera_id,
+        };
+
+        let mut sequence: u32 = 1;
+        let mut offset = init_segment.len() as u64;
+        let mut last_timestamp: Option<f64> = None;
+
+        loop {
+            tokio::select! {
+                _ = &mut cancel_rx => break,
+                frame = frames.recv() => {
+                    match frame {
+                        Ok(frame) => {
+                            // Fragment duration comes from the wall-clock gap between
+                            // frames, since that's also what lets export_clip place
+                            // each fragment on an absolute timeline.
+                            let duration_ms = last_timestamp
+                                .map(|prev| ((frame.received_at_unix_secs - prev) * 1000.0).round().max(1.0) as u32)
+                                .unwrap_or(1);
+                            last_timestamp = Some(frame.received_at_unix_secs);
+
+                            let fragment = moof_and_mdat(sequence, &frame.bytes, duration_ms);
+                            if let Err(e) = file.write_all(&fragment) {
+                                eprintln!("Failed to write recording fragment: {}", e);
+                                break;
+                            }
+
+                            let index_entry = FragmentIndexEntry {
+                                offset,
+                                length: fragment.len() as u64,
+                                timestamp: frame.received_at_unix_secs,
+                            };
+                            if let Ok(line) = serde_json::to_string(&index_entry) {
+                                let _ = writeln!(index_file, "{}", line);
+                            }
+
+                            offset += fragment.len() as u64;
+                            sequence += 1;
+                        }
+                        Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => continue,
+                        Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+                    }
+                }
+            }
+        }
+    });
+
+    let path_str = path.to_string_lossy().into_owned();
+    recordings.insert(
+        camera_id,
+        RecordingHandle {
+            task,
+            cancel: Some(cancel_tx),
+            path,
+        },
+    );
+
+    Ok(path_str)
+}
+
+#[tauri::command]
+pub async fn stop_recording(state: State<'_, AppState>, camera_id: String) -> Result<bool, String> {
+    let removed = state.recordings.lock().unwrap().remove(&camera_id);
+    Ok(removed.is_some())
+}
+
+/// Assembles an MP4 covering `[start_ts, end_ts]` (unix seconds) out of the
+/// individual fragments — not whole recording files — whose timestamp in
+/// the recording's `.index.jsonl` sidecar falls in that window. This lets a
+/// short clip be exported from the middle of a long-running recording
+/// instead of only matching whole files by their start time.
+#[tauri::command]
+pub async fn export_clip(
+    app_handle: AppHandle,
+    camera_id: String,
+    start_ts: u64,
+    end_ts: u64,
+) -> Result<String, String> {
+    let dir = recordings_dir(&app_handle)?;
+    let export_dir = dir.join("exports");
+    std::fs::create_dir_all(&export_dir)
+        .map_err(|e| format!("Failed to create {}: {}", export_dir.display(), e))?;
+
+    let prefix = format!("{}-", camera_id);
+    let recording_paths: Vec<PathBuf> = std::fs::read_dir(&dir)
+        .map_err(|e| format!("Failed to read {}: {}", dir.display(), e))?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.extension().and_then(|e| e.to_str()) == Some("mp4")
+                && path
+                    .file_name()
+                    .and_then(|n| n.to_str())
+                    .is_some_and(|n| n.starts_with(&prefix))
+        })
+        .collect();
+
+    let start = start_ts as f64;
+    let end = end_ts as f64;
+
+    let mut matching: Vec<(f64, PathBuf, FragmentIndexEntry)> = Vec::new();
+    for recording_path in &recording_paths {
+        let index_contents = match std::fs::read_to_string(index_path(recording_path)) {
+            Ok(contents) => contents,
+            Err(_) => continue, // no fragments flushed for this recording yet
+        };
+        for line in index_contents.lines() {
+            let Ok(entry) = serde_json::from_str::<FragmentIndexEntry>(line) else {
+                continue;
+            };
+            if entry.timestamp >= start && entry.timestamp <= end {
+                matching.push((entry.timestamp, recording_path.clone(), entry));
+            }
+        }
+    }
+    matching.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    if matching.is_empty() {
+        return Err(format!(
+            "No recorded fragments for camera {} between {} and {}",
+            camera_id, start_ts, end_ts
+        ));
+    }
+
+    let export_path = export_dir.join(format!("{}-{}-{}.mp4", camera_id, start_ts, end_ts));
+    let mut export_file =
+        File::create(&export_path).map_err(|e| format!("Failed to create export file: {}", e))?;
+
+    export_file
+        .write_all(&ftyp_box())
+        .and_then(|_| export_file.write_all(&moov_box()))
+        .map_err(|e| format!("Failed to write export init segment: {}", e))?;
+
+    let mut open_sources: HashMap<PathBuf, File> = HashMap::new();
+    for (_, recording_path, entry) in matching {
+        let source = match open_sources.get_mut(&recording_path) {
+            Some(file) => file,
+            None => {
+                let file = File::open(&recording_path)
+                    .map_err(|e| format!("Failed to open {}: {}", recording_path.display(), e))?;
+                open_sources.entry(recording_path.clone()).or_insert(file)
+            }
+        };
+
+        source
+            .seek(SeekFrom::Start(entry.offset))
+            .map_err(|e| format!("Failed to seek in {}: {}", recording_path.display(), e))?;
+        let mut buf = vec![0u8; entry.length as usize];
+        source
+            .read_exact(&mut buf)
+            .map_err(|e| format!("Failed to read fragment from {}: {}", recording_path.display(), e))?;
+        export_file
+            .write_all(&buf)
+            .map_err(|e| format!("Failed to append fragment to export: {}", e))?;
+    }
+
+    Ok(export_path.to_string_lossy().into_owned())
+}
@@ -0,0 +1,106 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use base64::Engine;
+use tauri::{AppHandle, Emitter, State};
+use tokio::sync::oneshot;
+
+use crate::camera_tap::{self, TapReleaseGuard};
+use crate::metrics;
+use crate::AppState;
+
+/// Handle to a live per-camera event-forwarding task.
+///
+/// Dropping the handle (or calling `stop`) signals the spawned task to stop
+/// forwarding frames and releases this viewer's subscription on the shared
+/// `camera_tap`, so a stream never outlives the camera tile that opened it.
+pub struct StreamHandle {
+    task: tauri::async_runtime::JoinHandle<()>,
+    cancel: Option<oneshot::Sender<()>>,
+}
+
+impl Drop for StreamHandle {
+    fn drop(&mut self) {
+        if let Some(cancel) = self.cancel.take() {
+            let _ = cancel.send(());
+        }
+        self.task.abort();
+    }
+}
+
+pub type StreamRegistry = Arc<Mutex<HashMap<String, StreamHandle>>>;
+
+#[tauri::command]
+pub async fn start_camera_stream(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+    camera_id: String,
+) -> Result<bool, String> {
+    if state.camera_streams.lock().unwrap().contains_key(&camera_id) {
+        // Already streaming; nothing to do.
+        return Ok(true);
+    }
+
+    // Connect (or join the existing tap) before registering anything, so a
+    // failed connect never leaves a dead entry behind that permanently
+    // no-ops future start calls.
+    let mut frames = camera_tap::acquire(&state, &camera_id).await?;
+
+    let mut streams = state.camera_streams.lock().unwrap();
+    if streams.contains_key(&camera_id) {
+        // Another call won the race while we were connecting.
+        camera_tap::release(&state, &camera_id);
+        return Ok(true);
+    }
+
+    let (cancel_tx, mut cancel_rx) = oneshot::channel();
+    let event_name = format!("camera-frame:{}", camera_id);
+    let release_state = Arc::new(state.inner().clone());
+    let release_camera_id = camera_id.clone();
+
+    let task = tauri::async_runtime::spawn(async move {
+        let _release_guard = TapReleaseGuard {
+            state: release_state,
+            camera_id: release_camera_id,
+        };
+
+        loop {
+            tokio::select! {
+                _ = &mut cancel_rx => break,
+                frame = frames.recv() => {
+                    match frame {
+                        Ok(frame) => {
+                            // Base64-encode before emitting: Tauri's emit serializes
+                            // the payload as JSON, and a raw Vec<u8> becomes a JSON
+                            // array of per-byte numbers — several times larger and
+                            // far slower to encode/decode than a base64 string.
+                            let encoded = base64::engine::general_purpose::STANDARD.encode(frame.bytes.as_slice());
+                            let _ = app_handle.emit(&event_name, encoded);
+                        }
+                        Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => continue,
+                        Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+                    }
+                }
+            }
+        }
+    });
+
+    streams.insert(
+        camera_id,
+        StreamHandle {
+            task,
+            cancel: Some(cancel_tx),
+        },
+    );
+    metrics::set_active_camera_streams(streams.len() as f64);
+
+    Ok(true)
+}
+
+#[tauri::command]
+pub async fn stop_camera_stream(state: State<'_, AppState>, camera_id: String) -> Result<bool, String> {
+    let mut streams = state.camera_streams.lock().unwrap();
+    let removed = streams.remove(&camera_id);
+    metrics::set_active_camera_streams(streams.len() as f64);
+    Ok(removed.is_some())
+}
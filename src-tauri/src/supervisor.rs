@@ -0,0 +1,142 @@
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use netstat2::{get_sockets_info, AddressFamilyFlags, ProtocolFlags, ProtocolSocketInfo, TcpState};
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+
+use crate::metrics;
+use crate::{start_python_backend_internal, AppState};
+
+const BACKEND_PORT: u16 = 8765;
+const HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+const MIN_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BackendStatus {
+    Starting,
+    Healthy,
+    Unhealthy,
+    Restarting,
+}
+
+#[derive(Serialize)]
+struct BackendStatusEvent {
+    status: BackendStatus,
+    restart_count: u32,
+}
+
+/// Returns true if a process we are tracking (by pid) owns the listening
+/// socket on `port`, as opposed to some unrelated process that happens to
+/// have the port bound.
+pub fn port_owned_by_pid(port: u16, pid: u32) -> bool {
+    let af_flags = AddressFamilyFlags::IPV4 | AddressFamilyFlags::IPV6;
+    let proto_flags = ProtocolFlags::TCP;
+
+    let sockets = match get_sockets_info(af_flags, proto_flags) {
+        Ok(sockets) => sockets,
+        Err(_) => return false,
+    };
+
+    sockets.into_iter().any(|socket| match socket.protocol_socket_info {
+        ProtocolSocketInfo::Tcp(tcp) => {
+            tcp.local_port == port
+                && tcp.state == TcpState::Listen
+                && socket.associated_pids.contains(&pid)
+        }
+        _ => false,
+    })
+}
+
+/// Returns true if anything at all is listening on `port`, regardless of
+/// which process owns it.
+pub fn port_in_use(port: u16) -> bool {
+    let af_flags = AddressFamilyFlags::IPV4 | AddressFamilyFlags::IPV6;
+    let proto_flags = ProtocolFlags::TCP;
+
+    let sockets = match get_sockets_info(af_flags, proto_flags) {
+        Ok(sockets) => sockets,
+        Err(_) => return false,
+    };
+
+    sockets.into_iter().any(|socket| match socket.protocol_socket_info {
+        ProtocolSocketInfo::Tcp(tcp) => tcp.local_port == port && tcp.state == TcpState::Listen,
+        _ => false,
+    })
+}
+
+async fn probe_health(state: &AppState) -> bool {
+    let url = format!("{}/api/health", state.base_url());
+    state
+        .authed(state.http_client().get(&url))
+        .timeout(Duration::from_secs(2))
+        .send()
+        .await
+        .map(|resp| resp.status().is_success())
+        .unwrap_or(false)
+}
+
+fn emit_status(app_handle: &AppHandle, status: BackendStatus, restart_count: &AtomicU32) {
+    let event = BackendStatusEvent {
+        status,
+        restart_count: restart_count.load(Ordering::Relaxed),
+    };
+    let _ = app_handle.emit("backend-status", &event);
+}
+
+/// Periodically probes the Python backend and restarts it with exponential
+/// backoff if it goes unresponsive or exits. Meant to be spawned once from
+/// `run()`'s setup and left running for the lifetime of the app.
+pub fn spawn(app_handle: AppHandle, state: Arc<AppState>) {
+    tauri::async_runtime::spawn(async move {
+        let restart_count = state.backend_restart_count.clone();
+        let mut backoff = MIN_BACKOFF;
+        let mut last_status = BackendStatus::Starting;
+        emit_status(&app_handle, last_status, &restart_count);
+
+        loop {
+            tokio::time::sleep(HEALTH_CHECK_INTERVAL).await;
+
+            if probe_health(&state).await {
+                if last_status != BackendStatus::Healthy {
+                    last_status = BackendStatus::Healthy;
+                    emit_status(&app_handle, BackendStatus::Healthy, &restart_count);
+                    metrics::set_backend_running(true);
+                }
+                backoff = MIN_BACKOFF;
+                continue;
+            }
+
+            last_status = BackendStatus::Unhealthy;
+            emit_status(&app_handle, BackendStatus::Unhealthy, &restart_count);
+            metrics::set_backend_running(false);
+
+            last_status = BackendStatus::Restarting;
+            emit_status(&app_handle, BackendStatus::Restarting, &restart_count);
+
+            {
+                let mut process_guard = state.python_process.lock().unwrap();
+                if let Some(mut child) = process_guard.take() {
+                    let _ = child.kill();
+                    let _ = child.wait();
+                }
+            }
+
+            match start_python_backend_internal(state.clone()).await {
+                Ok(_) => {
+                    restart_count.fetch_add(1, Ordering::Relaxed);
+                    metrics::record_backend_restart();
+                }
+                Err(e) => {
+                    eprintln!("Supervisor failed to restart Python backend: {}", e);
+                }
+            }
+
+            tokio::time::sleep(backoff).await;
+            backoff = (backoff * 2).min(MAX_BACKOFF);
+        }
+    });
+}
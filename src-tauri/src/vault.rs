@@ -0,0 +1,244 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use aes_gcm::aead::{Aead, KeyInit};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use argon2::Argon2;
+use base64::Engine;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, State};
+use zeroize::Zeroize;
+
+use crate::AppState;
+
+const VAULT_FILE_NAME: &str = "credentials.vault.json";
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+const KEY_LEN: usize = 32;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct StoredCredential {
+    nonce: String,
+    ciphertext: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct VaultFile {
+    salt: String,
+    credentials: HashMap<String, StoredCredential>,
+}
+
+impl VaultFile {
+    fn path(app_handle: &AppHandle) -> Result<PathBuf, String> {
+        let dir = app_handle
+            .path()
+            .app_config_dir()
+            .map_err(|e| format!("Failed to resolve app config directory: {}", e))?;
+        Ok(dir.join(VAULT_FILE_NAME))
+    }
+
+    fn load(app_handle: &AppHandle) -> Result<Self, String> {
+        let path = Self::path(app_handle)?;
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let contents = fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        serde_json::from_str(&contents).map_err(|e| format!("Failed to parse vault file: {}", e))
+    }
+
+    fn save(&self, app_handle: &AppHandle) -> Result<(), String> {
+        let path = Self::path(app_handle)?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+        }
+        let contents =
+            serde_json::to_string_pretty(self).map_err(|e| format!("Failed to serialize vault: {}", e))?;
+        fs::write(&path, contents).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    }
+}
+
+/// Decrypted username/password pair, zeroed out of memory on drop so a
+/// secret never lingers past the single camera command that needed it.
+pub struct Credential {
+    pub username: String,
+    pub password: String,
+}
+
+impl Drop for Credential {
+    fn drop(&mut self) {
+        self.username.zeroize();
+        self.password.zeroize();
+    }
+}
+
+/// Encrypted-at-rest credential store. Credentials are persisted through
+/// `VaultFile` regardless of lock state; the derived Argon2id key only
+/// lives in memory while unlocked and is required to decrypt any entry.
+pub struct Vault {
+    key: Mutex<Option<[u8; KEY_LEN]>>,
+}
+
+impl Vault {
+    pub fn new() -> Self {
+        Self {
+            key: Mutex::new(None),
+        }
+    }
+
+    fn derive_key(passphrase: &str, salt: &[u8]) -> Result<[u8; KEY_LEN], String> {
+        let mut key = [0u8; KEY_LEN];
+        Argon2::default()
+            .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+            .map_err(|e| format!("Failed to derive vault key: {}", e))?;
+        Ok(key)
+    }
+
+    pub fn unlock(&self, app_handle: &AppHandle, passphrase: &str) -> Result<(), String> {
+        let mut vault_file = VaultFile::load(app_handle)?;
+
+        if vault_file.salt.is_empty() {
+            let mut salt = vec![0u8; SALT_LEN];
+            rand::thread_rng().fill_bytes(&mut salt);
+            vault_file.salt = base64::engine::general_purpose::STANDARD.encode(&salt);
+            vault_file.save(app_handle)?;
+        }
+
+        let salt = base64::engine::general_purpose::STANDARD
+            .decode(&vault_file.salt)
+            .map_err(|e| format!("Corrupt vault salt: {}", e))?;
+
+        let key = Self::derive_key(passphrase, &salt)?;
+        *self.key.lock().unwrap() = Some(key);
+        Ok(())
+    }
+
+    pub fn lock(&self) {
+        if let Some(mut key) = self.key.lock().unwrap().take() {
+            key.zeroize();
+        }
+    }
+
+    pub fn is_unlocked(&self) -> bool {
+        self.key.lock().unwrap().is_some()
+    }
+
+    fn cipher(&self) -> Result<Aes256Gcm, String> {
+        let key = self.key.lock().unwrap().ok_or("Vault is locked")?;
+        Ok(Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key)))
+    }
+
+    /// Encrypts `username`/`password` and persists them under the
+    /// caller-supplied `credential_id` (replacing any entry with that id),
+    /// for later use with `add_camera`/`test_camera`.
+    pub fn store_credential(
+        &self,
+        app_handle: &AppHandle,
+        credential_id: &str,
+        username: &str,
+        password: &str,
+    ) -> Result<(), String> {
+        let cipher = self.cipher()?;
+
+        let mut nonce_bytes = [0u8; NONCE_LEN];
+        rand::thread_rng().fill_bytes(&mut nonce_bytes);
+        let nonce = Nonce::from_slice(&nonce_bytes);
+
+        let plaintext = serde_json::json!({ "username": username, "password": password }).to_string();
+        let ciphertext = cipher
+            .encrypt(nonce, plaintext.as_bytes())
+            .map_err(|e| format!("Failed to encrypt credential: {}", e))?;
+
+        let mut vault_file = VaultFile::load(app_handle)?;
+        vault_file.credentials.insert(
+            credential_id.to_string(),
+            StoredCredential {
+                nonce: base64::engine::general_purpose::STANDARD.encode(nonce_bytes),
+                ciphertext: base64::engine::general_purpose::STANDARD.encode(&ciphertext),
+            },
+        );
+        vault_file.save(app_handle)
+    }
+
+    /// Decrypts the credential for `credential_id`. Only valid while the
+    /// vault is unlocked; the result must be dropped promptly by the caller.
+    pub fn decrypt_credential(
+        &self,
+        app_handle: &AppHandle,
+        credential_id: &str,
+    ) -> Result<Credential, String> {
+        let cipher = self.cipher()?;
+        let vault_file = VaultFile::load(app_handle)?;
+
+        let stored = vault_file
+            .credentials
+            .get(credential_id)
+            .ok_or_else(|| format!("No credential stored with id {}", credential_id))?;
+
+        let nonce_bytes = base64::engine::general_purpose::STANDARD
+            .decode(&stored.nonce)
+            .map_err(|e| format!("Corrupt credential nonce: {}", e))?;
+        let ciphertext = base64::engine::general_purpose::STANDARD
+            .decode(&stored.ciphertext)
+            .map_err(|e| format!("Corrupt credential ciphertext: {}", e))?;
+
+        if nonce_bytes.len() != NONCE_LEN {
+            return Err(format!(
+                "Corrupt credential nonce: expected {} bytes, got {}",
+                NONCE_LEN,
+                nonce_bytes.len()
+            ));
+        }
+
+        let mut plaintext = cipher
+            .decrypt(Nonce::from_slice(&nonce_bytes), ciphertext.as_slice())
+            .map_err(|e| format!("Failed to decrypt credential (wrong passphrase?): {}", e))?;
+
+        let parsed: serde_json::Value = serde_json::from_slice(&plaintext)
+            .map_err(|e| format!("Corrupt decrypted credential: {}", e))?;
+        plaintext.zeroize();
+
+        let username = parsed["username"]
+            .as_str()
+            .ok_or("Decrypted credential missing username")?
+            .to_string();
+        let password = parsed["password"]
+            .as_str()
+            .ok_or("Decrypted credential missing password")?
+            .to_string();
+
+        Ok(Credential { username, password })
+    }
+}
+
+#[tauri::command]
+pub async fn unlock_vault(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+    passphrase: String,
+) -> Result<(), String> {
+    state.vault.unlock(&app_handle, &passphrase)
+}
+
+#[tauri::command]
+pub async fn lock_vault(state: State<'_, AppState>) -> Result<(), String> {
+    state.vault.lock();
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn store_credential(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+    credential_id: String,
+    username: String,
+    password: String,
+) -> Result<(), String> {
+    state
+        .vault
+        .store_credential(&app_handle, &credential_id, &username, &password)
+}